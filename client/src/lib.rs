@@ -1,4 +1,11 @@
-use spacetimedb::{table, reducer, Identity, ReducerContext, Table, Timestamp};
+use std::time::Duration;
+
+use spacetimedb::{table, reducer, Identity, ReducerContext, ScheduleAt, Table, Timestamp};
+
+const PHYSICS_TICK_INTERVAL: Duration = Duration::from_millis(50);
+const DEFAULT_MAX_SPEED_M_S: f64 = 250.0;
+const MAX_THRUST_M_S2: f64 = 20.0;
+const WARP_DURATION: Duration = Duration::from_secs(2);
 
 #[table(name = user, public)]
 pub struct User {
@@ -22,9 +29,31 @@ pub struct Ship {
     pub vy: f64,
     pub vz: f64,
 
+    // Desired acceleration from the last `set_thrust` call; applied each physics tick.
+    pub ax: f64,
+    pub ay: f64,
+    pub az: f64,
+
+    pub max_speed_m_s: f64,
+
+    // While set, the ship is warping: the tick zeroes velocity and keeps position fixed
+    // until `warping_until`, then snaps to `warp_target_*` in one shot.
+    pub warping_until: Option<Timestamp>,
+    pub warp_target_x: f64,
+    pub warp_target_y: f64,
+    pub warp_target_z: f64,
+
     pub updated_at: Timestamp,
 }
 
+#[table(name = physics_tick_timer, scheduled(physics_tick))]
+pub struct PhysicsTickTimer {
+    #[primary_key]
+    #[auto_inc]
+    pub scheduled_id: u64,
+    pub scheduled_at: ScheduleAt,
+}
+
 fn validate_name(name: String) -> Result<String, String> {
     let name = name.trim().to_string();
     if name.is_empty() {
@@ -59,35 +88,24 @@ pub fn set_name(ctx: &ReducerContext, name: String) -> Result<(), String> {
     }
 }
 
+/// Set the ship's desired acceleration vector. The server integrates motion from this on
+/// the next physics tick; clients no longer write position/velocity directly.
 #[reducer]
-/// Update ship state (client-authoritative for this MVP).
-pub fn set_ship_state(
-    ctx: &ReducerContext,
-    px: f64,
-    py: f64,
-    pz: f64,
-    vx: f64,
-    vy: f64,
-    vz: f64,
-) -> Result<(), String> {
-    let px = finite(px)?;
-    let py = finite(py)?;
-    let pz = finite(pz)?;
-    let vx = finite(vx)?;
-    let vy = finite(vy)?;
-    let vz = finite(vz)?;
+pub fn set_thrust(ctx: &ReducerContext, ax: f64, ay: f64, az: f64) -> Result<(), String> {
+    let ax = finite(ax)?;
+    let ay = finite(ay)?;
+    let az = finite(az)?;
+
+    let mag = (ax * ax + ay * ay + az * az).sqrt();
+    let (ax, ay, az) = if mag > MAX_THRUST_M_S2 && mag > 0.0 {
+        let scale = MAX_THRUST_M_S2 / mag;
+        (ax * scale, ay * scale, az * scale)
+    } else {
+        (ax, ay, az)
+    };
 
     if let Some(ship) = ctx.db.ship().owner().find(ctx.sender) {
-        ctx.db.ship().owner().update(Ship {
-            owner: ship.owner,
-            px,
-            py,
-            pz,
-            vx,
-            vy,
-            vz,
-            updated_at: ctx.timestamp,
-        });
+        ctx.db.ship().owner().update(Ship { ax, ay, az, ..ship });
         Ok(())
     } else {
         Err("No ship found for user".to_string())
@@ -95,7 +113,8 @@ pub fn set_ship_state(
 }
 
 #[reducer]
-/// Warp ship to a position (instant for MVP).
+/// Begin a warp to a position. Velocity is zeroed immediately; the server snaps the ship
+/// to the target position once `WARP_DURATION` has elapsed.
 pub fn warp_to(ctx: &ReducerContext, px: f64, py: f64, pz: f64) -> Result<(), String> {
     let px = finite(px)?;
     let py = finite(py)?;
@@ -103,14 +122,18 @@ pub fn warp_to(ctx: &ReducerContext, px: f64, py: f64, pz: f64) -> Result<(), St
 
     if let Some(ship) = ctx.db.ship().owner().find(ctx.sender) {
         ctx.db.ship().owner().update(Ship {
-            owner: ship.owner,
-            px,
-            py,
-            pz,
             vx: 0.0,
             vy: 0.0,
             vz: 0.0,
+            ax: 0.0,
+            ay: 0.0,
+            az: 0.0,
+            warping_until: Some(ctx.timestamp + WARP_DURATION),
+            warp_target_x: px,
+            warp_target_y: py,
+            warp_target_z: pz,
             updated_at: ctx.timestamp,
+            ..ship
         });
         Ok(())
     } else {
@@ -118,6 +141,70 @@ pub fn warp_to(ctx: &ReducerContext, px: f64, py: f64, pz: f64) -> Result<(), St
     }
 }
 
+/// Scheduled reducer driving server-authoritative motion: integrates every ship's velocity
+/// and position at a fixed dt, resolving any in-progress warp along the way.
+#[reducer]
+pub fn physics_tick(ctx: &ReducerContext, _timer: PhysicsTickTimer) -> Result<(), String> {
+    if ctx.sender != ctx.identity() {
+        return Err("physics_tick may only be invoked by the scheduler".to_string());
+    }
+
+    let dt = PHYSICS_TICK_INTERVAL.as_secs_f64();
+
+    for ship in ctx.db.ship().iter().collect::<Vec<_>>() {
+        if let Some(warping_until) = ship.warping_until {
+            if ctx.timestamp >= warping_until {
+                ctx.db.ship().owner().update(Ship {
+                    px: ship.warp_target_x,
+                    py: ship.warp_target_y,
+                    pz: ship.warp_target_z,
+                    vx: 0.0,
+                    vy: 0.0,
+                    vz: 0.0,
+                    warping_until: None,
+                    updated_at: ctx.timestamp,
+                    ..ship
+                });
+            }
+            // Still warping: hold position, velocity stays zeroed.
+            continue;
+        }
+
+        let mut vx = ship.vx + ship.ax * dt;
+        let mut vy = ship.vy + ship.ay * dt;
+        let mut vz = ship.vz + ship.az * dt;
+
+        let speed = (vx * vx + vy * vy + vz * vz).sqrt();
+        if speed > ship.max_speed_m_s && speed > 0.0 {
+            let scale = ship.max_speed_m_s / speed;
+            vx *= scale;
+            vy *= scale;
+            vz *= scale;
+        }
+
+        ctx.db.ship().owner().update(Ship {
+            px: ship.px + vx * dt,
+            py: ship.py + vy * dt,
+            pz: ship.pz + vz * dt,
+            vx,
+            vy,
+            vz,
+            updated_at: ctx.timestamp,
+            ..ship
+        });
+    }
+
+    Ok(())
+}
+
+#[reducer(init)]
+pub fn init(ctx: &ReducerContext) {
+    ctx.db.physics_tick_timer().insert(PhysicsTickTimer {
+        scheduled_id: 0, // auto_inc
+        scheduled_at: PHYSICS_TICK_INTERVAL.into(),
+    });
+}
+
 #[reducer(client_connected)]
 /// Called automatically when a client connects.
 /// This special reducer pattern is documented in the Rust quickstart. :contentReference[oaicite:9]{index=9}
@@ -146,6 +233,14 @@ pub fn client_connected(ctx: &ReducerContext) {
             vx: 0.0,
             vy: 0.0,
             vz: 0.0,
+            ax: 0.0,
+            ay: 0.0,
+            az: 0.0,
+            max_speed_m_s: DEFAULT_MAX_SPEED_M_S,
+            warping_until: None,
+            warp_target_x: 0.0,
+            warp_target_y: 0.0,
+            warp_target_z: 0.0,
             updated_at: ctx.timestamp,
         });
     }
@@ -1,16 +1,54 @@
-use spacetimedb::{Identity, ReducerContext};
+use rand::Rng;
+use spacetimedb::{Identity, ReducerContext, Timestamp};
 
 const DEFAULT_CARGO_CAPACITY_M3: u32 = 160;
+const DEFAULT_HANGAR_CAPACITY_M3: u32 = 2_000;
 
-// Placeholder loot
+// This module cannot read the movement module's authoritative `Ship` table (separate
+// SpacetimeDB modules, no shared workspace here), so `report_position` plausibility-checks
+// reported movement against elapsed time instead of trusting it outright. Kept a little
+// above the movement module's own ship speed cap to allow for clock/tick slack.
+const MAX_PLAUSIBLE_SPEED_M_S: f64 = 300.0;
+
+// Clients are expected to call `report_position` at roughly this cadence. Elapsed time is
+// clamped to this before computing the allowed travel distance, so going idle and then
+// reporting a single huge jump doesn't bank up an unbounded travel budget.
+const MAX_REPORT_INTERVAL_S: f64 = 5.0;
+
+// `spawn_wreck` is still client-callable (placeholder until NPCs are authoritative
+// server-side) and rolls real loot, so each identity is limited to one wreck every
+// `WRECK_SPAWN_COOLDOWN_S` to keep it from being an unlimited rare-loot faucet.
+const WRECK_SPAWN_COOLDOWN_S: f64 = 30.0;
+
+// Placeholder loot, used when a site has no configured drop table.
 const ITEM_SALVAGED_SCRAP: u16 = 1;
 const ITEM_SALVAGED_SCRAP_VOLUME_M3: u32 = 10;
+const ITEM_SALVAGED_SCRAP_MAX_STACK: u32 = 500;
+
+fn finite(v: f64) -> Result<f64, String> {
+    if v.is_finite() {
+        Ok(v)
+    } else {
+        Err("Non-finite number rejected".to_string())
+    }
+}
 
 #[spacetimedb::table(name = player, public)]
 pub struct Player {
     #[primary_key]
     pub identity: Identity,
     pub cargo_capacity_m3: u32,
+    pub hangar_capacity_m3: u32,
+
+    // Last reported ship position, used for station docking-range checks in this module.
+    pub pos_x_m: f64,
+    pub pos_y_m: f64,
+    pub pos_z_m: f64,
+    pub last_reported_at: Timestamp,
+
+    // Last time this identity successfully spawned a wreck via `spawn_wreck`, used to
+    // rate-limit that reducer. `None` until the first spawn.
+    pub last_wreck_spawn_at: Option<Timestamp>,
 }
 
 #[spacetimedb::table(name = player_item, public)]
@@ -24,7 +62,6 @@ pub struct PlayerItem {
 
     pub item_type: u16,
     pub quantity: u32,
-    pub volume_m3: u32,
 }
 
 #[spacetimedb::table(name = wreck, public)]
@@ -51,11 +88,136 @@ pub struct WreckItem {
 
     pub item_type: u16,
     pub quantity: u32,
+}
+
+/// A weighted rare-drop chart for a site: `rolls` independent draws over `drop_table_entry`
+/// rows sharing this `site_id`; each roll also gets an independent `1 in rare_one_in` chance
+/// to add a bonus rare item.
+#[spacetimedb::table(name = drop_table, public)]
+pub struct DropTable {
+    #[primary_key]
+    pub site_id: u64,
+
+    pub rolls: u32,
+
+    pub rare_item_type: Option<u16>,
+    pub rare_min_qty: u32,
+    pub rare_max_qty: u32,
+    pub rare_one_in: Option<u32>,
+}
+
+#[spacetimedb::table(name = drop_table_entry, public)]
+pub struct DropTableEntry {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+
+    #[index(btree)]
+    pub site_id: u64,
+
+    pub item_type: u16,
+    pub weight: u32,
+    pub min_qty: u32,
+    pub max_qty: u32,
+}
+
+/// Persistent station storage, separate from ship cargo. Deposits/withdrawals only work
+/// while the player is within docking range of a `Station`.
+#[spacetimedb::table(name = hangar_item, public)]
+pub struct HangarItem {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+
+    #[index(btree)]
+    pub owner: Identity,
+
+    pub item_type: u16,
+    pub quantity: u32,
+}
+
+/// Authoritative item catalog. Replaces bare `u16` item-type constants and per-row volume
+/// duplication: `add_to_inventory`/`add_to_hangar`/the loot path all look up `volume_m3`
+/// and `max_stack` from here instead of trusting the caller or a wreck row.
+#[spacetimedb::table(name = item_def, public)]
+pub struct ItemDef {
+    #[primary_key]
+    pub item_type: u16,
+
+    pub name: String,
     pub volume_m3: u32,
+    pub max_stack: u32,
+    pub category: u8,
+}
+
+#[spacetimedb::table(name = station, public)]
+pub struct Station {
+    #[primary_key]
+    pub id: u64,
+
+    pub pos_x_m: f64,
+    pub pos_y_m: f64,
+    pub pos_z_m: f64,
+    pub dock_range_m: f64,
+}
+
+/// An in-progress two-party item trade. The swap only executes once both sides have
+/// locked their offers and confirmed; until then nothing moves between inventories.
+#[spacetimedb::table(name = trade_session, public)]
+pub struct TradeSession {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+
+    pub party_a: Identity,
+    pub party_b: Identity,
+
+    pub a_locked: bool,
+    pub b_locked: bool,
+    pub a_confirmed: bool,
+    pub b_confirmed: bool,
+}
+
+#[spacetimedb::table(name = trade_offer, public)]
+pub struct TradeOffer {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+
+    #[index(btree)]
+    pub session_id: u64,
+
+    pub owner: Identity,
+    pub player_item_id: u64,
+    pub quantity: u32,
 }
 
 #[spacetimedb::reducer(init)]
-pub fn init(_ctx: &ReducerContext) {}
+pub fn init(ctx: &ReducerContext) {
+    ctx.db.item_def().insert(ItemDef {
+        item_type: ITEM_SALVAGED_SCRAP,
+        name: "Salvaged Scrap".to_string(),
+        volume_m3: ITEM_SALVAGED_SCRAP_VOLUME_M3,
+        max_stack: ITEM_SALVAGED_SCRAP_MAX_STACK,
+        category: 0,
+    });
+}
+
+fn item_def_or_err(ctx: &ReducerContext, item_type: u16) -> Result<ItemDef, String> {
+    ctx.db
+        .item_def()
+        .item_type()
+        .find(item_type)
+        .ok_or_else(|| format!("Unknown item_type {item_type}: not registered in item_def"))
+}
+
+fn item_volume_m3(ctx: &ReducerContext, item_type: u16) -> Result<u32, String> {
+    item_def_or_err(ctx, item_type).map(|d| d.volume_m3)
+}
+
+fn item_max_stack(ctx: &ReducerContext, item_type: u16) -> Result<u32, String> {
+    item_def_or_err(ctx, item_type).map(|d| d.max_stack.max(1))
+}
 
 #[spacetimedb::reducer(client_connected)]
 pub fn client_connected(ctx: &ReducerContext) {
@@ -64,17 +226,97 @@ pub fn client_connected(ctx: &ReducerContext) {
         ctx.db.player().insert(Player {
             identity: ctx.sender,
             cargo_capacity_m3: DEFAULT_CARGO_CAPACITY_M3,
+            hangar_capacity_m3: DEFAULT_HANGAR_CAPACITY_M3,
+            pos_x_m: 0.0,
+            pos_y_m: 0.0,
+            pos_z_m: 0.0,
+            last_reported_at: ctx.timestamp,
+            last_wreck_spawn_at: None,
         });
     }
 }
 
+fn elapsed_secs(from: Timestamp, to: Timestamp) -> f64 {
+    let micros = to.to_micros_since_unix_epoch() - from.to_micros_since_unix_epoch();
+    micros.max(0) as f64 / 1_000_000.0
+}
+
+/// Report the player's current ship position, used only for station docking-range checks
+/// in this module. Since this module has no direct view of the movement module's
+/// authoritative `Ship` state, the reported jump is rejected if it implies a speed beyond
+/// `MAX_PLAUSIBLE_SPEED_M_S` for the elapsed time since the last report, with that elapsed
+/// time capped at `MAX_REPORT_INTERVAL_S` so going idle doesn't bank a travel budget large
+/// enough to "teleport" to a station in one report.
+#[spacetimedb::reducer]
+pub fn report_position(ctx: &ReducerContext, pos_x_m: f64, pos_y_m: f64, pos_z_m: f64) -> Result<(), String> {
+    let pos_x_m = finite(pos_x_m)?;
+    let pos_y_m = finite(pos_y_m)?;
+    let pos_z_m = finite(pos_z_m)?;
+
+    let player = ctx
+        .db
+        .player()
+        .identity()
+        .find(ctx.sender)
+        .ok_or_else(|| "Player not found".to_string())?;
+
+    let dx = pos_x_m - player.pos_x_m;
+    let dy = pos_y_m - player.pos_y_m;
+    let dz = pos_z_m - player.pos_z_m;
+    let distance_m = (dx * dx + dy * dy + dz * dz).sqrt();
+
+    let elapsed_s = elapsed_secs(player.last_reported_at, ctx.timestamp).min(MAX_REPORT_INTERVAL_S);
+    let max_distance_m = MAX_PLAUSIBLE_SPEED_M_S * elapsed_s;
+
+    if distance_m > max_distance_m {
+        return Err("Reported position implies an impossible speed".to_string());
+    }
+
+    ctx.db.player().identity().update(Player {
+        pos_x_m,
+        pos_y_m,
+        pos_z_m,
+        last_reported_at: ctx.timestamp,
+        ..player
+    });
+
+    Ok(())
+}
+
 /// Client can request spawning a wreck (placeholder until NPCs are authoritative server-side).
+/// Since this rolls real loot, each identity is limited to one spawn per
+/// `WRECK_SPAWN_COOLDOWN_S` to keep it from being an unlimited loot faucet.
 #[spacetimedb::reducer]
-pub fn spawn_wreck(ctx: &ReducerContext, wreck_id: u64, site_id: u64, pos_x_m: f64, pos_y_m: f64, pos_z_m: f64) {
+pub fn spawn_wreck(
+    ctx: &ReducerContext,
+    wreck_id: u64,
+    site_id: u64,
+    pos_x_m: f64,
+    pos_y_m: f64,
+    pos_z_m: f64,
+) -> Result<(), String> {
+    let player = ctx
+        .db
+        .player()
+        .identity()
+        .find(ctx.sender)
+        .ok_or_else(|| "Player not found".to_string())?;
+
+    if let Some(last_spawn) = player.last_wreck_spawn_at {
+        if elapsed_secs(last_spawn, ctx.timestamp) < WRECK_SPAWN_COOLDOWN_S {
+            return Err("spawn_wreck is on cooldown for this identity".to_string());
+        }
+    }
+
     if ctx.db.wreck().id().find(wreck_id).is_some() {
-        return;
+        return Ok(());
     }
 
+    ctx.db.player().identity().update(Player {
+        last_wreck_spawn_at: Some(ctx.timestamp),
+        ..player
+    });
+
     ctx.db.wreck().insert(Wreck {
         id: wreck_id,
         site_id,
@@ -83,49 +325,281 @@ pub fn spawn_wreck(ctx: &ReducerContext, wreck_id: u64, site_id: u64, pos_x_m: f
         pos_z_m,
     });
 
-    // One placeholder item
-    ctx.db.wreck_item().insert(WreckItem {
-        id: 0, // auto_inc
-        wreck_id,
-        item_type: ITEM_SALVAGED_SCRAP,
-        quantity: 1,
-        volume_m3: ITEM_SALVAGED_SCRAP_VOLUME_M3,
-    });
+    roll_wreck_loot(ctx, wreck_id, site_id);
+
+    Ok(())
+}
+
+/// Populate a freshly spawned wreck's contents from the `site_id`'s drop table, falling back
+/// to a single placeholder scrap item when no table is configured for the site.
+fn roll_wreck_loot(ctx: &ReducerContext, wreck_id: u64, site_id: u64) {
+    let Some(table) = ctx.db.drop_table().site_id().find(site_id) else {
+        ctx.db.wreck_item().insert(WreckItem {
+            id: 0, // auto_inc
+            wreck_id,
+            item_type: ITEM_SALVAGED_SCRAP,
+            quantity: 1,
+        });
+        return;
+    };
+
+    let entries: Vec<DropTableEntry> = ctx
+        .db
+        .drop_table_entry()
+        .site_id()
+        .filter(site_id)
+        .collect();
+
+    let total_weight: u32 = entries.iter().fold(0u32, |acc, e| acc.saturating_add(e.weight));
+
+    for _ in 0..table.rolls {
+        if total_weight > 0 {
+            let draw = ctx.rng().gen_range(0..total_weight);
+            let mut acc = 0u32;
+            for entry in &entries {
+                acc += entry.weight;
+                if draw < acc {
+                    let (lo, hi) = (entry.min_qty.min(entry.max_qty), entry.min_qty.max(entry.max_qty));
+                    let quantity = ctx.rng().gen_range(lo..=hi);
+                    ctx.db.wreck_item().insert(WreckItem {
+                        id: 0, // auto_inc
+                        wreck_id,
+                        item_type: entry.item_type,
+                        quantity,
+                    });
+                    break;
+                }
+            }
+        }
+
+        if let (Some(rare_item_type), Some(rare_one_in)) = (table.rare_item_type, table.rare_one_in) {
+            if rare_one_in > 0 && ctx.rng().gen_range(0..rare_one_in) == 0 {
+                let (lo, hi) = (table.rare_min_qty.min(table.rare_max_qty), table.rare_min_qty.max(table.rare_max_qty));
+                let quantity = ctx.rng().gen_range(lo..=hi);
+                ctx.db.wreck_item().insert(WreckItem {
+                    id: 0, // auto_inc
+                    wreck_id,
+                    item_type: rare_item_type,
+                    quantity,
+                });
+            }
+        }
+    }
 }
 
-fn cargo_used_m3(ctx: &ReducerContext, owner: Identity) -> u32 {
+fn cargo_used_m3(ctx: &ReducerContext, owner: Identity) -> Result<u32, String> {
     ctx.db
         .player_item()
         .iter()
         .filter(|r| r.owner == owner)
-        .fold(0u32, |acc, r| acc.saturating_add(r.quantity.saturating_mul(r.volume_m3)))
+        .try_fold(0u32, |acc, r| {
+            Ok(acc.saturating_add(r.quantity.saturating_mul(item_volume_m3(ctx, r.item_type)?)))
+        })
 }
 
-fn add_to_inventory(ctx: &ReducerContext, owner: Identity, item_type: u16, volume_m3: u32, quantity: u32) {
+/// Merge `quantity` into an existing matching stack up to `max_stack`, spilling any
+/// remainder into additional new rows. Shared by `add_to_inventory` (cargo) and
+/// `add_to_hangar` (station storage) via closures, since each backs a different generated
+/// table type.
+fn merge_or_insert_stack<R: Clone>(
+    mut quantity: u32,
+    max_stack: u32,
+    current_quantity: impl Fn(&R) -> u32,
+    find_existing: impl FnOnce() -> Option<R>,
+    update_existing: impl FnOnce(R, u32),
+    mut insert_new: impl FnMut(u32),
+) {
     if quantity == 0 {
         return;
     }
 
-    // Merge into an existing stack if present.
-    if let Some(existing) = ctx
+    if let Some(existing) = find_existing() {
+        let room = max_stack.saturating_sub(current_quantity(&existing));
+        let add = quantity.min(room);
+        if add > 0 {
+            let new_quantity = current_quantity(&existing) + add;
+            update_existing(existing, new_quantity);
+            quantity -= add;
+        }
+    }
+
+    while quantity > 0 {
+        let take = quantity.min(max_stack);
+        insert_new(take);
+        quantity -= take;
+    }
+}
+
+fn add_to_inventory(ctx: &ReducerContext, owner: Identity, item_type: u16, quantity: u32) -> Result<(), String> {
+    let max_stack = item_max_stack(ctx, item_type)?;
+
+    merge_or_insert_stack(
+        quantity,
+        max_stack,
+        |existing: &PlayerItem| existing.quantity,
+        || {
+            ctx.db
+                .player_item()
+                .iter()
+                .find(|r| r.owner == owner && r.item_type == item_type)
+        },
+        |existing, new_quantity| {
+            ctx.db.player_item().id().update(PlayerItem { quantity: new_quantity, ..existing });
+        },
+        |quantity| {
+            ctx.db.player_item().insert(PlayerItem {
+                id: 0, // auto_inc
+                owner,
+                item_type,
+                quantity,
+            });
+        },
+    );
+
+    Ok(())
+}
+
+fn hangar_used_m3(ctx: &ReducerContext, owner: Identity) -> Result<u32, String> {
+    ctx.db
+        .hangar_item()
+        .iter()
+        .filter(|r| r.owner == owner)
+        .try_fold(0u32, |acc, r| {
+            Ok(acc.saturating_add(r.quantity.saturating_mul(item_volume_m3(ctx, r.item_type)?)))
+        })
+}
+
+fn add_to_hangar(ctx: &ReducerContext, owner: Identity, item_type: u16, quantity: u32) -> Result<(), String> {
+    let max_stack = item_max_stack(ctx, item_type)?;
+
+    merge_or_insert_stack(
+        quantity,
+        max_stack,
+        |existing: &HangarItem| existing.quantity,
+        || {
+            ctx.db
+                .hangar_item()
+                .iter()
+                .find(|r| r.owner == owner && r.item_type == item_type)
+        },
+        |existing, new_quantity| {
+            ctx.db.hangar_item().id().update(HangarItem { quantity: new_quantity, ..existing });
+        },
+        |quantity| {
+            ctx.db.hangar_item().insert(HangarItem {
+                id: 0, // auto_inc
+                owner,
+                item_type,
+                quantity,
+            });
+        },
+    );
+
+    Ok(())
+}
+
+fn require_docked(ctx: &ReducerContext, player: &Player) -> Result<(), String> {
+    let docked = ctx.db.station().iter().any(|s| {
+        let dx = player.pos_x_m - s.pos_x_m;
+        let dy = player.pos_y_m - s.pos_y_m;
+        let dz = player.pos_z_m - s.pos_z_m;
+        (dx * dx + dy * dy + dz * dz).sqrt() <= s.dock_range_m
+    });
+
+    if docked {
+        Ok(())
+    } else {
+        Err("Not within docking range of a station".to_string())
+    }
+}
+
+/// Move an item stack from ship cargo into station hangar storage. Requires docking range.
+#[spacetimedb::reducer]
+pub fn deposit_item(ctx: &ReducerContext, player_item_id: u64, quantity: u32) -> Result<(), String> {
+    let player = ctx
+        .db
+        .player()
+        .identity()
+        .find(ctx.sender)
+        .ok_or_else(|| "Player not found".to_string())?;
+
+    require_docked(ctx, &player)?;
+
+    let item = ctx
         .db
         .player_item()
-        .iter()
-        .find(|r| r.owner == owner && r.item_type == item_type && r.volume_m3 == volume_m3)
-    {
-        let mut updated = existing.clone();
-        updated.quantity = updated.quantity.saturating_add(quantity);
+        .id()
+        .find(player_item_id)
+        .ok_or_else(|| "Item not found".to_string())?;
+
+    if item.owner != ctx.sender {
+        return Err("You do not own this item".to_string());
+    }
+    if quantity == 0 || quantity > item.quantity {
+        return Err("Invalid quantity".to_string());
+    }
+
+    let free_hangar_m3 = player.hangar_capacity_m3.saturating_sub(hangar_used_m3(ctx, ctx.sender)?);
+    if quantity.saturating_mul(item_volume_m3(ctx, item.item_type)?) > free_hangar_m3 {
+        return Err("Not enough free hangar space".to_string());
+    }
+
+    if item.quantity == quantity {
+        ctx.db.player_item().id().delete(item.id);
+    } else {
+        let mut updated = item.clone();
+        updated.quantity -= quantity;
         ctx.db.player_item().id().update(updated);
-        return;
     }
 
-    ctx.db.player_item().insert(PlayerItem {
-        id: 0, // auto_inc
-        owner,
-        item_type,
-        quantity,
-        volume_m3,
-    });
+    add_to_hangar(ctx, ctx.sender, item.item_type, quantity)?;
+
+    Ok(())
+}
+
+/// Move an item stack from station hangar storage into ship cargo. Requires docking range
+/// and enough free cargo space.
+#[spacetimedb::reducer]
+pub fn withdraw_item(ctx: &ReducerContext, hangar_item_id: u64, quantity: u32) -> Result<(), String> {
+    let player = ctx
+        .db
+        .player()
+        .identity()
+        .find(ctx.sender)
+        .ok_or_else(|| "Player not found".to_string())?;
+
+    require_docked(ctx, &player)?;
+
+    let item = ctx
+        .db
+        .hangar_item()
+        .id()
+        .find(hangar_item_id)
+        .ok_or_else(|| "Item not found".to_string())?;
+
+    if item.owner != ctx.sender {
+        return Err("You do not own this item".to_string());
+    }
+    if quantity == 0 || quantity > item.quantity {
+        return Err("Invalid quantity".to_string());
+    }
+
+    let free_m3 = player.cargo_capacity_m3.saturating_sub(cargo_used_m3(ctx, ctx.sender)?);
+    if quantity.saturating_mul(item_volume_m3(ctx, item.item_type)?) > free_m3 {
+        return Err("Not enough free cargo space".to_string());
+    }
+
+    if item.quantity == quantity {
+        ctx.db.hangar_item().id().delete(item.id);
+    } else {
+        let mut updated = item.clone();
+        updated.quantity -= quantity;
+        ctx.db.hangar_item().id().update(updated);
+    }
+
+    add_to_inventory(ctx, ctx.sender, item.item_type, quantity)?;
+
+    Ok(())
 }
 
 /// Loot as much as fits; if everything is looted, the wreck is removed.
@@ -147,7 +621,7 @@ pub fn loot_all(ctx: &ReducerContext, wreck_id: u64) -> Result<(), String> {
 
     let mut free_m3 = player
         .cargo_capacity_m3
-        .saturating_sub(cargo_used_m3(ctx, ctx.sender));
+        .saturating_sub(cargo_used_m3(ctx, ctx.sender)?);
 
     // Collect wreck items first (so we can mutate/delete safely)
     let mut items: Vec<WreckItem> = ctx
@@ -164,14 +638,14 @@ pub fn loot_all(ctx: &ReducerContext, wreck_id: u64) -> Result<(), String> {
             break;
         }
 
-        let per_unit = wi.volume_m3.max(1);
+        let per_unit = item_volume_m3(ctx, wi.item_type)?.max(1);
         let max_take = free_m3 / per_unit;
         if max_take == 0 {
             continue;
         }
 
         let take_qty = wi.quantity.min(max_take);
-        add_to_inventory(ctx, ctx.sender, wi.item_type, wi.volume_m3, take_qty);
+        add_to_inventory(ctx, ctx.sender, wi.item_type, take_qty)?;
 
         free_m3 = free_m3.saturating_sub(take_qty.saturating_mul(per_unit));
 
@@ -184,16 +658,351 @@ pub fn loot_all(ctx: &ReducerContext, wreck_id: u64) -> Result<(), String> {
         }
     }
 
-    // If no items remain, delete wreck
-    let any_left = ctx
-        .db
-        .wreck_item()
-        .iter()
-        .any(|r| r.wreck_id == wreck_id);
+    delete_wreck_if_empty(ctx, wreck.id);
+
+    Ok(())
+}
 
+fn delete_wreck_if_empty(ctx: &ReducerContext, wreck_id: u64) {
+    let any_left = ctx.db.wreck_item().iter().any(|r| r.wreck_id == wreck_id);
     if !any_left {
-        ctx.db.wreck().id().delete(wreck.id);
+        ctx.db.wreck().id().delete(wreck_id);
+    }
+}
+
+/// Grab a specific batch of wreck items by id/quantity. Unlike `loot_all`'s best-effort
+/// greedy grab, this is all-or-nothing: if the batch's total volume doesn't fit in current
+/// free cargo, nothing is taken.
+#[spacetimedb::reducer]
+pub fn loot_items(ctx: &ReducerContext, wreck_id: u64, picks: Vec<(u64, u32)>) -> Result<(), String> {
+    ctx.db
+        .wreck()
+        .id()
+        .find(wreck_id)
+        .ok_or_else(|| "Wreck not found".to_string())?;
+
+    let player = ctx
+        .db
+        .player()
+        .identity()
+        .find(ctx.sender)
+        .ok_or_else(|| "Player not found".to_string())?;
+
+    // Accumulate requested quantity per `wreck_item_id` first: a `picks` array referencing
+    // the same id more than once must be validated against its combined total, not checked
+    // against the original row's quantity on every occurrence (which would let duplicate
+    // entries credit more than the row ever held).
+    let mut requested: std::collections::HashMap<u64, u32> = std::collections::HashMap::new();
+    let mut order: Vec<u64> = Vec::new();
+
+    for (wreck_item_id, quantity) in picks {
+        if quantity == 0 {
+            return Err("Invalid quantity requested".to_string());
+        }
+        if !requested.contains_key(&wreck_item_id) {
+            order.push(wreck_item_id);
+        }
+        let total = requested.entry(wreck_item_id).or_insert(0);
+        *total = total.saturating_add(quantity);
+    }
+
+    let mut picked: Vec<(WreckItem, u32)> = Vec::with_capacity(order.len());
+    let mut total_m3 = 0u32;
+
+    for wreck_item_id in order {
+        let wi = ctx
+            .db
+            .wreck_item()
+            .id()
+            .find(wreck_item_id)
+            .ok_or_else(|| "Wreck item not found".to_string())?;
+
+        if wi.wreck_id != wreck_id {
+            return Err("Item does not belong to this wreck".to_string());
+        }
+
+        let quantity = requested[&wreck_item_id];
+        if quantity > wi.quantity {
+            return Err("Invalid quantity requested".to_string());
+        }
+
+        total_m3 = total_m3.saturating_add(quantity.saturating_mul(item_volume_m3(ctx, wi.item_type)?));
+        picked.push((wi, quantity));
+    }
+
+    let free_m3 = player.cargo_capacity_m3.saturating_sub(cargo_used_m3(ctx, ctx.sender)?);
+    if total_m3 > free_m3 {
+        return Err("Not enough free cargo space for this batch".to_string());
+    }
+
+    for (wi, quantity) in picked {
+        add_to_inventory(ctx, ctx.sender, wi.item_type, quantity)?;
+
+        if quantity == wi.quantity {
+            ctx.db.wreck_item().id().delete(wi.id);
+        } else {
+            let mut updated = wi.clone();
+            updated.quantity -= quantity;
+            ctx.db.wreck_item().id().update(updated);
+        }
+    }
+
+    delete_wreck_if_empty(ctx, wreck_id);
+
+    Ok(())
+}
+
+fn find_trade_session(ctx: &ReducerContext, session_id: u64) -> Result<TradeSession, String> {
+    ctx.db
+        .trade_session()
+        .id()
+        .find(session_id)
+        .ok_or_else(|| "Trade session not found".to_string())
+}
+
+fn other_party(session: &TradeSession, caller: Identity) -> Result<Identity, String> {
+    if caller == session.party_a {
+        Ok(session.party_b)
+    } else if caller == session.party_b {
+        Ok(session.party_a)
+    } else {
+        Err("Not a party to this trade".to_string())
+    }
+}
+
+/// Open a trade session with another player. Nothing is offered or moved until both
+/// sides lock and confirm.
+#[spacetimedb::reducer]
+pub fn trade_open(ctx: &ReducerContext, other: Identity) -> Result<u64, String> {
+    if other == ctx.sender {
+        return Err("Cannot trade with yourself".to_string());
+    }
+
+    let session = ctx.db.trade_session().insert(TradeSession {
+        id: 0, // auto_inc
+        party_a: ctx.sender,
+        party_b: other,
+        a_locked: false,
+        b_locked: false,
+        a_confirmed: false,
+        b_confirmed: false,
+    });
+
+    Ok(session.id)
+}
+
+/// Offer an item stack into an open trade. Does not move the item; only locks in the
+/// caller's intent. Adding after either side has locked resets both confirmations.
+#[spacetimedb::reducer]
+pub fn trade_add_item(
+    ctx: &ReducerContext,
+    session_id: u64,
+    player_item_id: u64,
+    quantity: u32,
+) -> Result<(), String> {
+    let session = find_trade_session(ctx, session_id)?;
+    other_party(&session, ctx.sender)?;
+
+    if quantity == 0 {
+        return Err("Quantity must be positive".to_string());
+    }
+
+    let item = ctx
+        .db
+        .player_item()
+        .id()
+        .find(player_item_id)
+        .ok_or_else(|| "Item not found".to_string())?;
+
+    if item.owner != ctx.sender {
+        return Err("You do not own this item".to_string());
+    }
+
+    let already_offered: u32 = ctx
+        .db
+        .trade_offer()
+        .session_id()
+        .filter(session_id)
+        .filter(|o| o.owner == ctx.sender && o.player_item_id == player_item_id)
+        .fold(0u32, |acc, o| acc.saturating_add(o.quantity));
+
+    if already_offered.saturating_add(quantity) > item.quantity {
+        return Err("Not enough quantity to offer".to_string());
+    }
+
+    ctx.db.trade_offer().insert(TradeOffer {
+        id: 0, // auto_inc
+        session_id,
+        owner: ctx.sender,
+        player_item_id,
+        quantity,
+    });
+
+    if session.a_locked || session.b_locked {
+        ctx.db.trade_session().id().update(TradeSession {
+            a_confirmed: false,
+            b_confirmed: false,
+            ..session
+        });
+    }
+
+    Ok(())
+}
+
+/// Lock the caller's side of the trade. Locking does not block further `trade_add_item`
+/// calls; any offer added after either side has locked clears both confirmations.
+#[spacetimedb::reducer]
+pub fn trade_lock(ctx: &ReducerContext, session_id: u64) -> Result<(), String> {
+    let session = find_trade_session(ctx, session_id)?;
+
+    if ctx.sender == session.party_a {
+        ctx.db.trade_session().id().update(TradeSession { a_locked: true, ..session });
+    } else if ctx.sender == session.party_b {
+        ctx.db.trade_session().id().update(TradeSession { b_locked: true, ..session });
+    } else {
+        return Err("Not a party to this trade".to_string());
+    }
+
+    Ok(())
+}
+
+/// Confirm the caller's side of a locked trade. Once both sides are confirmed, the swap
+/// executes atomically. If it fails validation, the reducer returns an error and all of its
+/// changes, including this confirmation, are rolled back; the session stays open.
+#[spacetimedb::reducer]
+pub fn trade_confirm(ctx: &ReducerContext, session_id: u64) -> Result<(), String> {
+    let session = find_trade_session(ctx, session_id)?;
+    other_party(&session, ctx.sender)?;
+
+    if !session.a_locked || !session.b_locked {
+        return Err("Both sides must lock before confirming".to_string());
+    }
+
+    let session = if ctx.sender == session.party_a {
+        ctx.db.trade_session().id().update(TradeSession { a_confirmed: true, ..session })
+    } else {
+        ctx.db.trade_session().id().update(TradeSession { b_confirmed: true, ..session })
+    };
+
+    if session.a_confirmed && session.b_confirmed {
+        execute_trade(ctx, &session)?;
     }
 
     Ok(())
 }
+
+/// Cancel a trade session, discarding any offers. No items have moved, so this is a no-op
+/// on inventories.
+#[spacetimedb::reducer]
+pub fn trade_cancel(ctx: &ReducerContext, session_id: u64) -> Result<(), String> {
+    let session = find_trade_session(ctx, session_id)?;
+    other_party(&session, ctx.sender)?;
+
+    delete_trade(ctx, session_id);
+
+    Ok(())
+}
+
+fn delete_trade(ctx: &ReducerContext, session_id: u64) {
+    let offer_ids: Vec<u64> = ctx
+        .db
+        .trade_offer()
+        .session_id()
+        .filter(session_id)
+        .map(|o| o.id)
+        .collect();
+
+    for id in offer_ids {
+        ctx.db.trade_offer().id().delete(id);
+    }
+
+    ctx.db.trade_session().id().delete(session_id);
+}
+
+/// Execute both sides of a confirmed trade atomically: re-validate ownership and cargo
+/// space, then transfer every offered stack via `add_to_inventory`.
+fn execute_trade(ctx: &ReducerContext, session: &TradeSession) -> Result<(), String> {
+    let offers: Vec<TradeOffer> = ctx.db.trade_offer().session_id().filter(session.id).collect();
+
+    // Validate every offer still holds, and tally incoming/outgoing volume per party.
+    let mut incoming_a_m3 = 0u32;
+    let mut incoming_b_m3 = 0u32;
+    let mut outgoing_a_m3 = 0u32;
+    let mut outgoing_b_m3 = 0u32;
+    let mut offered_per_item: std::collections::HashMap<u64, u32> = std::collections::HashMap::new();
+
+    for offer in &offers {
+        let item = ctx
+            .db
+            .player_item()
+            .id()
+            .find(offer.player_item_id)
+            .ok_or_else(|| "Offered item no longer exists".to_string())?;
+
+        let offered = offered_per_item.entry(offer.player_item_id).or_insert(0);
+        *offered = offered.saturating_add(offer.quantity);
+        if item.owner != offer.owner || item.quantity < *offered {
+            return Err("Offered item changed before confirmation".to_string());
+        }
+
+        let volume = offer.quantity.saturating_mul(item_volume_m3(ctx, item.item_type)?);
+        if offer.owner == session.party_a {
+            outgoing_a_m3 = outgoing_a_m3.saturating_add(volume);
+            incoming_b_m3 = incoming_b_m3.saturating_add(volume);
+        } else {
+            outgoing_b_m3 = outgoing_b_m3.saturating_add(volume);
+            incoming_a_m3 = incoming_a_m3.saturating_add(volume);
+        }
+    }
+
+    let player_a = ctx
+        .db
+        .player()
+        .identity()
+        .find(session.party_a)
+        .ok_or_else(|| "Player not found".to_string())?;
+    let player_b = ctx
+        .db
+        .player()
+        .identity()
+        .find(session.party_b)
+        .ok_or_else(|| "Player not found".to_string())?;
+
+    // Net out each party's own outgoing items first: they vacate cargo space that their
+    // incoming items can then occupy.
+    let free_a = player_a
+        .cargo_capacity_m3
+        .saturating_sub(cargo_used_m3(ctx, session.party_a)?.saturating_sub(outgoing_a_m3));
+    let free_b = player_b
+        .cargo_capacity_m3
+        .saturating_sub(cargo_used_m3(ctx, session.party_b)?.saturating_sub(outgoing_b_m3));
+
+    if incoming_a_m3 > free_a || incoming_b_m3 > free_b {
+        return Err("Not enough free cargo space to complete the trade".to_string());
+    }
+
+    for offer in &offers {
+        let item = ctx
+            .db
+            .player_item()
+            .id()
+            .find(offer.player_item_id)
+            .ok_or_else(|| "Offered item no longer exists".to_string())?;
+
+        let recipient = if offer.owner == session.party_a { session.party_b } else { session.party_a };
+
+        if item.quantity == offer.quantity {
+            ctx.db.player_item().id().delete(item.id);
+        } else {
+            let mut updated = item.clone();
+            updated.quantity -= offer.quantity;
+            ctx.db.player_item().id().update(updated);
+        }
+
+        add_to_inventory(ctx, recipient, item.item_type, offer.quantity)?;
+    }
+
+    delete_trade(ctx, session.id);
+
+    Ok(())
+}